@@ -1,4 +1,5 @@
-use secure_log::SecureLogger;
+use secrecy::Secret;
+use secure_log::{Cipher, SecureLogger};
 use log::{error, warn, info, debug, trace};
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -6,7 +7,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
   let log_path = "example.log";
 
   // Initialize the encrypted logger
-  let _logger = SecureLogger::encrypt(key.to_string(), log_path)?;
+  let _logger = SecureLogger::encrypt(Secret::new(key.to_string()), log_path, Cipher::Aes256Gcm)?;
 
   // Log some messages
   error!("This is an error message log");
@@ -25,7 +26,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
   // Decrypt and display contents
   println!("*** Decrypted Log Contents ***");
-  let decrypted = SecureLogger::decrypt(key.to_string(), log_path)?;
+  let decrypted = SecureLogger::decrypt(Secret::new(key.to_string()), log_path)?;
   println!("{}", decrypted);
 
   Ok(())
@@ -1,35 +1,47 @@
 //! Secure logging for sensitive application data
 //!
 //! Provides encrypted logging capabilities with the following features:
-//! - AES-256-GCM encryption for all log messages
+//! - AES-256-GCM or XChaCha20-Poly1305 encryption for all log messages
 //! - Asynchronous processing via background thread to minimize performance impact
-//! - Automatic key derivation using SHA-256
+//! - Salted Argon2id key derivation, with a per-file random salt
 //! - Compatible with the standard `log` crate interface
 //! - Built-in message queuing with backpressure
+//! - Compile-time level filtering via `max_level_*` Cargo features
+//! - Tamper-evident ordering via a per-session sequence number and level
+//!   bound as AEAD associated data on every entry
+//! - Streaming, constant-memory decryption via [`SecureLogger::decrypt_stream`]
+//! - Allocation-lean encryption hot path using reused scratch buffers and
+//!   batched writes
 //!
 //! # Security Considerations
 //! - Messages are encrypted before being written to disk
 //! - Each log entry uses a unique random nonce
 //! - The encryption key never touches the disk
+//! - The passphrase is wrapped in [`secrecy::Secret`] and derived key
+//!   bytes are zeroized as soon as the cipher has copied them
 //! - Messages are queued in memory only temporarily before encryption
 
 use std::{
   fs::{File, OpenOptions},
-  io::{BufRead, BufReader, Write},
+  io::{BufRead, BufReader, Lines, Write},
   path::{Path, PathBuf},
   thread::{self, JoinHandle},
   sync::{Once, Arc},
 };
 use aes_gcm::{
-  aead::{Aead, KeyInit},
+  aead::{Aead, AeadInPlace, KeyInit, Payload},
   Aes256Gcm, Nonce,
 };
+use argon2::Argon2;
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
 use chrono::Utc;
 use crossbeam_channel::{bounded, Sender};
 use log::{Level, LevelFilter, Metadata, Record};
 use rand::{rngs::OsRng, RngCore};
+use secrecy::{ExposeSecret, Secret};
 use sha2::{Sha256, Digest};
+use zeroize::Zeroize;
 
 mod error;
 pub use error::Error;
@@ -37,12 +49,320 @@ pub use error::Error;
 /// Size of the message queue for the background logging thread
 const QUEUE_SIZE: usize = 10_000;
 
+// The `max_level_*` features statically cap the verbosity that this crate
+// will ever emit, mirroring the same feature set on the `log` crate itself.
+// Picking, say, `max_level_warn` in a release build means trace/debug call
+// sites are skipped before any formatting or channel send ever happens,
+// rather than merely being filtered at runtime.
+//
+// Features are meant to be mutually exclusive, but Cargo feature unification
+// across a workspace can enable more than one without the user intending it,
+// so guard against that the same way the `log` crate does: fail the build
+// with a clear error instead of silently picking one or (worse) defining
+// `STATIC_MAX_LEVEL` twice.
+#[cfg(any(
+  all(feature = "max_level_off", feature = "max_level_error"),
+  all(feature = "max_level_off", feature = "max_level_warn"),
+  all(feature = "max_level_off", feature = "max_level_info"),
+  all(feature = "max_level_off", feature = "max_level_debug"),
+  all(feature = "max_level_off", feature = "max_level_trace"),
+  all(feature = "max_level_error", feature = "max_level_warn"),
+  all(feature = "max_level_error", feature = "max_level_info"),
+  all(feature = "max_level_error", feature = "max_level_debug"),
+  all(feature = "max_level_error", feature = "max_level_trace"),
+  all(feature = "max_level_warn", feature = "max_level_info"),
+  all(feature = "max_level_warn", feature = "max_level_debug"),
+  all(feature = "max_level_warn", feature = "max_level_trace"),
+  all(feature = "max_level_info", feature = "max_level_debug"),
+  all(feature = "max_level_info", feature = "max_level_trace"),
+  all(feature = "max_level_debug", feature = "max_level_trace"),
+))]
+compile_error!("at most one `max_level_*` feature may be enabled at a time");
+
+#[cfg(feature = "max_level_off")]
+const STATIC_MAX_LEVEL: LevelFilter = LevelFilter::Off;
+#[cfg(all(feature = "max_level_error", not(feature = "max_level_off")))]
+const STATIC_MAX_LEVEL: LevelFilter = LevelFilter::Error;
+#[cfg(all(
+  feature = "max_level_warn",
+  not(any(feature = "max_level_off", feature = "max_level_error"))
+))]
+const STATIC_MAX_LEVEL: LevelFilter = LevelFilter::Warn;
+#[cfg(all(
+  feature = "max_level_info",
+  not(any(
+    feature = "max_level_off",
+    feature = "max_level_error",
+    feature = "max_level_warn"
+  ))
+))]
+const STATIC_MAX_LEVEL: LevelFilter = LevelFilter::Info;
+#[cfg(all(
+  feature = "max_level_debug",
+  not(any(
+    feature = "max_level_off",
+    feature = "max_level_error",
+    feature = "max_level_warn",
+    feature = "max_level_info"
+  ))
+))]
+const STATIC_MAX_LEVEL: LevelFilter = LevelFilter::Debug;
+#[cfg(all(
+  feature = "max_level_trace",
+  not(any(
+    feature = "max_level_off",
+    feature = "max_level_error",
+    feature = "max_level_warn",
+    feature = "max_level_info",
+    feature = "max_level_debug"
+  ))
+))]
+const STATIC_MAX_LEVEL: LevelFilter = LevelFilter::Trace;
+#[cfg(not(any(
+  feature = "max_level_off",
+  feature = "max_level_error",
+  feature = "max_level_warn",
+  feature = "max_level_info",
+  feature = "max_level_debug",
+  feature = "max_level_trace"
+)))]
+const STATIC_MAX_LEVEL: LevelFilter = LevelFilter::Trace;
+
 static INIT: Once = Once::new();
 
+/// Magic string identifying the plaintext KDF header on the first line of
+/// a log file produced by a version of this crate that salts its keys.
+/// Files without this prefix on their first line are assumed to predate
+/// the header and are decrypted with the legacy unsalted SHA-256 path.
+const HEADER_MAGIC: &str = "secure_log/v1";
+
+/// Length in bytes of the random salt generated for each encrypted file
+const SALT_LEN: usize = 16;
+
+/// Argon2id memory cost in KiB
+const ARGON2_M_COST: u32 = 19_456;
+
+/// Argon2id iteration count
+const ARGON2_T_COST: u32 = 2;
+
+/// Argon2id parallelism degree
+const ARGON2_P_COST: u32 = 1;
+
+/// Upper bounds on the Argon2id parameters accepted from a file header.
+/// A header is untrusted input (an attacker who can edit the file can edit
+/// these fields), and `argon2::Params::new` happily accepts values far
+/// beyond anything a real header would need, so without a cap a tampered
+/// `m=` field can make `hash_password_into` try to allocate gigabytes and
+/// abort the process instead of returning a catchable error. These ceilings
+/// are generous relative to [`ARGON2_M_COST`]/[`ARGON2_T_COST`]/
+/// [`ARGON2_P_COST`] but far below what would threaten the process.
+const ARGON2_MAX_M_COST: u32 = 262_144; // 256 MiB in KiB
+const ARGON2_MAX_T_COST: u32 = 16;
+const ARGON2_MAX_P_COST: u32 = 8;
+
+/// Selects which AEAD algorithm encrypts log entries
+///
+/// AES-256-GCM's 12-byte random nonce risks a birthday-bound collision
+/// after roughly 2^32 messages, which a long-running high-volume logger
+/// can plausibly reach. [`Cipher::XChaCha20Poly1305`] uses a 24-byte
+/// random nonce, making collisions negligible even at extreme volumes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Cipher {
+  Aes256Gcm,
+  XChaCha20Poly1305,
+}
+
+impl Cipher {
+  /// Nonce length in bytes for this algorithm
+  fn nonce_len(self) -> usize {
+    match self {
+      Self::Aes256Gcm => 12,
+      Self::XChaCha20Poly1305 => 24,
+    }
+  }
+
+  /// Identifier recorded in the file header so `decrypt` knows which
+  /// algorithm and nonce length to use
+  fn id(self) -> &'static str {
+    match self {
+      Self::Aes256Gcm => "aes256gcm",
+      Self::XChaCha20Poly1305 => "xchacha20poly1305",
+    }
+  }
+
+  fn from_id(id: &str) -> Option<Self> {
+    match id {
+      "aes256gcm" => Some(Self::Aes256Gcm),
+      "xchacha20poly1305" => Some(Self::XChaCha20Poly1305),
+      _ => None,
+    }
+  }
+}
+
+/// Holds the concrete keyed cipher for whichever [`Cipher`] was selected
+///
+/// `Aes256Gcm` is boxed because its precomputed table is far larger than
+/// `XChaCha20Poly1305`'s state, and we don't want every `CipherImpl` to pay
+/// for the larger variant's size regardless of which one is in use.
+enum CipherImpl {
+  Aes256Gcm(Box<Aes256Gcm>),
+  XChaCha20Poly1305(XChaCha20Poly1305),
+}
+
+impl CipherImpl {
+  fn new(cipher: Cipher, key: &[u8]) -> Result<Self, Error> {
+    match cipher {
+      Cipher::Aes256Gcm => Aes256Gcm::new_from_slice(key)
+        .map(|c| Self::Aes256Gcm(Box::new(c)))
+        .map_err(|_| Error::InvalidKey),
+      Cipher::XChaCha20Poly1305 => XChaCha20Poly1305::new_from_slice(key)
+        .map(Self::XChaCha20Poly1305)
+        .map_err(|_| Error::InvalidKey),
+    }
+  }
+
+  /// Encrypt `buffer` in place, binding `aad` as associated data that is
+  /// authenticated but left in the clear. On success `buffer` holds the
+  /// ciphertext and tag; this avoids the extra allocation that returning a
+  /// fresh `Vec` per call would require on a hot logging path.
+  fn encrypt_in_place(&self, nonce: &[u8], aad: &[u8], buffer: &mut Vec<u8>) -> Result<(), ()> {
+    match self {
+      Self::Aes256Gcm(c) => c
+        .encrypt_in_place(Nonce::from_slice(nonce), aad, buffer)
+        .map_err(|_| ()),
+      Self::XChaCha20Poly1305(c) => c
+        .encrypt_in_place(XNonce::from_slice(nonce), aad, buffer)
+        .map_err(|_| ()),
+    }
+  }
+
+  /// Decrypt `msg`, verifying it was sealed with the same `aad`
+  fn decrypt(&self, nonce: &[u8], msg: &[u8], aad: &[u8]) -> Result<Vec<u8>, ()> {
+    let payload = Payload { msg, aad };
+    match self {
+      Self::Aes256Gcm(c) => c.decrypt(Nonce::from_slice(nonce), payload).map_err(|_| ()),
+      Self::XChaCha20Poly1305(c) => c.decrypt(XNonce::from_slice(nonce), payload).map_err(|_| ()),
+    }
+  }
+}
+
+/// Parameters recorded in a log file's plaintext header so that `decrypt`
+/// can reconstruct the exact Argon2id invocation and AEAD algorithm used
+/// at `encrypt` time
+struct KdfHeader {
+  cipher: Cipher,
+  m_cost: u32,
+  t_cost: u32,
+  p_cost: u32,
+  salt: Vec<u8>,
+}
+
+impl KdfHeader {
+  /// Render this header as the plaintext first line of a log file
+  fn to_line(&self) -> String {
+    format!(
+      "{};kdf=argon2id;cipher={};m={};t={};p={};salt={}",
+      HEADER_MAGIC,
+      self.cipher.id(),
+      self.m_cost,
+      self.t_cost,
+      self.p_cost,
+      BASE64.encode(&self.salt)
+    )
+  }
+
+  /// Parse a header line, returning `Ok(None)` if it isn't one of ours so
+  /// the caller can fall back to treating the line as a legacy encrypted
+  /// entry. Once the magic prefix matches, the line can only be one of our
+  /// headers, so any further malformed or out-of-range field is reported as
+  /// `Err(Error::InvalidData)` rather than silently falling back.
+  fn from_line(line: &str) -> Result<Option<Self>, Error> {
+    let mut fields = line.split(';');
+
+    match fields.next() {
+      Some(magic) if magic == HEADER_MAGIC => {}
+      _ => return Ok(None),
+    }
+
+    // `cipher_field` distinguishes "no `cipher=` field at all" (a header
+    // written before `Cipher` existed, which implied AES-256-GCM) from
+    // "`cipher=` was present but didn't name a known algorithm" (a typo or
+    // a tampered field) — the two must not be treated the same, or a
+    // corrupted/unknown cipher id would silently decrypt as AES-256-GCM
+    // with the wrong nonce length instead of failing outright.
+    let mut cipher_field = None;
+    let (mut m_cost, mut t_cost, mut p_cost, mut salt) = (None, None, None, None);
+
+    for field in fields {
+      let (key, value) = field.split_once('=').ok_or(Error::InvalidData)?;
+      match key {
+        "kdf" if value != "argon2id" => return Err(Error::InvalidData),
+        "cipher" => cipher_field = Some(Cipher::from_id(value)),
+        "m" => m_cost = value.parse().ok(),
+        "t" => t_cost = value.parse().ok(),
+        "p" => p_cost = value.parse().ok(),
+        "salt" => salt = BASE64.decode(value).ok(),
+        _ => {}
+      }
+    }
+
+    let cipher = match cipher_field {
+      None => Cipher::Aes256Gcm,
+      Some(Some(cipher)) => cipher,
+      Some(None) => return Err(Error::InvalidData),
+    };
+
+    let (m_cost, t_cost, p_cost, salt) =
+      match (m_cost, t_cost, p_cost, salt) {
+        (Some(m_cost), Some(t_cost), Some(p_cost), Some(salt)) => (m_cost, t_cost, p_cost, salt),
+        _ => return Err(Error::InvalidData),
+      };
+
+    // Reject out-of-range Argon2id parameters before they ever reach
+    // `Argon2::hash_password_into`, which has no such ceiling of its own.
+    if m_cost > ARGON2_MAX_M_COST || t_cost > ARGON2_MAX_T_COST || p_cost > ARGON2_MAX_P_COST {
+      return Err(Error::InvalidData);
+    }
+
+    Ok(Some(Self {
+      cipher,
+      m_cost,
+      t_cost,
+      p_cost,
+      salt,
+    }))
+  }
+}
+
+/// A derived encryption key that zeroes its bytes when dropped, so the
+/// key material doesn't linger in freed heap pages once the cipher has
+/// copied it into its own internal state
+struct DerivedKey(Vec<u8>);
+
+impl std::ops::Deref for DerivedKey {
+  type Target = [u8];
+
+  fn deref(&self) -> &[u8] {
+    &self.0
+  }
+}
+
+impl Drop for DerivedKey {
+  fn drop(&mut self) {
+    self.0.zeroize();
+  }
+}
+
 /// Message types that can be sent to the background logging thread
 enum LogMessage {
   /// A log entry pending encryption and writing
-  Entry(String),
+  Entry {
+    /// Log level, bound as associated data so it survives in the clear
+    level: Level,
+
+    /// Formatted message body to encrypt
+    text: String,
+  },
 
   /// Signals the background thread to finish processing and exit
   Shutdown,
@@ -63,23 +383,45 @@ impl SecureLogger {
   /// # Arguments
   /// * `key` - Encryption key (can be any string)
   /// * `log_path` - Path where encrypted logs will be written
+  /// * `cipher` - AEAD algorithm to encrypt entries with
   ///
   /// # Example
   /// ```no_run
-  /// use secure_log::SecureLogger;
+  /// use secrecy::Secret;
+  /// use secure_log::{Cipher, SecureLogger};
   ///
   /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
-  /// let logger = SecureLogger::encrypt("my-secret-key", "application.log")?;
+  /// let logger = SecureLogger::encrypt(
+  ///   Secret::new("my-secret-key".to_string()),
+  ///   "application.log",
+  ///   Cipher::Aes256Gcm,
+  /// )?;
   /// # Ok(())
   /// # }
   /// ```
-  pub fn encrypt(key: String, log_path: impl Into<PathBuf>) -> Result<Self, Error> {
-    // Derive 32-byte key bytes
-    let key_bytes = Self::derive_key(&key);
+  pub fn encrypt(
+    key: Secret<String>,
+    log_path: impl Into<PathBuf>,
+    cipher: Cipher,
+  ) -> Result<Self, Error> {
+    // Generate a random per-file salt and derive the key with Argon2id
+    let mut salt = vec![0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+
+    let header = KdfHeader {
+      cipher,
+      m_cost: ARGON2_M_COST,
+      t_cost: ARGON2_T_COST,
+      p_cost: ARGON2_P_COST,
+      salt,
+    };
 
-    // Create cipher from the provided key
-    let cipher = Aes256Gcm::new_from_slice(&key_bytes)
-      .map_err(|_| Error::InvalidKey)?;
+    // Create cipher from the provided key, then immediately drop the
+    // derived key bytes now that the cipher holds its own copy
+    let cipher_impl = {
+      let key_bytes = Self::derive_key_argon2(key.expose_secret(), &header)?;
+      CipherImpl::new(cipher, &key_bytes)?
+    };
 
     // Create channel for message passing
     let (sender, receiver) = bounded(QUEUE_SIZE);
@@ -91,35 +433,99 @@ impl SecureLogger {
       .truncate(true)
       .open(log_path.into())?;
 
+    // Write the plaintext KDF header as the first line so `decrypt` can
+    // reconstruct the Argon2id parameters and salt used here
+    writeln!(file, "{}", header.to_line())?;
+    file.flush()?;
+
     // Spawn background worker thread
     let worker = thread::Builder::new()
       .name("secure-logger".into())
       .spawn(move || {
-        while let Ok(message) = receiver.recv() {
-          match message {
-            LogMessage::Entry(log_entry) => {
-              // Generate random nonce for this message
-              let mut nonce_bytes = [0u8; 12];
-              OsRng.fill_bytes(&mut nonce_bytes);
-              let nonce = Nonce::from_slice(&nonce_bytes);
-
-              // Attempt to encrypt the log entry
-              if let Ok(encrypted) = cipher.encrypt(
-                nonce,
-                log_entry.as_bytes()
-              ) {
-                // Combine nonce and encrypted data
-                let mut combined = nonce.to_vec();
-                combined.extend_from_slice(&encrypted);
-
-                // Base64 encode and write to file
-                let encoded = BASE64.encode(combined);
-                let _ = writeln!(file, "{}", encoded);
-                let _ = file.flush();
+        // Monotonic per-session sequence number, bound as AEAD associated
+        // data alongside the level so `decrypt` can detect reordered or
+        // dropped lines without decrypting the message bodies
+        let mut seq: u64 = 0;
+
+        use std::fmt::Write as _;
+
+        // Reused scratch buffers for the hot path below: `ciphertext_buf`
+        // holds the plaintext-turned-ciphertext for one entry,
+        // `nonce_buf` its random nonce, `aad_buf` its "seq;level" associated
+        // data, `base64_buf` its encoded form, and `batch_buf` the lines for
+        // a whole drained batch. Each is `clear()`-ed and refilled every
+        // iteration instead of being reallocated, so steady-state logging
+        // does no per-message heap allocation beyond what the cipher itself
+        // requires.
+        let mut nonce_buf = [0u8; 24];
+        let mut aad_buf = String::new();
+        let mut ciphertext_buf: Vec<u8> = Vec::new();
+        let mut base64_buf: Vec<u8> = Vec::new();
+        let mut batch_buf: Vec<u8> = Vec::new();
+
+        'outer: while let Ok(first) = receiver.recv() {
+          // Drain whatever else is already queued so the whole batch can
+          // be written with a single `write_all` and a single `flush`
+          // rather than one syscall per message.
+          batch_buf.clear();
+          let mut shutdown = false;
+
+          for message in std::iter::once(first).chain(std::iter::from_fn(|| receiver.try_recv().ok())) {
+            match message {
+              LogMessage::Entry { level, text } => {
+                let nonce_len = cipher.nonce_len();
+                let nonce = &mut nonce_buf[..nonce_len];
+                OsRng.fill_bytes(nonce);
+
+                aad_buf.clear();
+                let _ = write!(aad_buf, "{};{}", seq, level);
+                let aad = &aad_buf;
+
+                ciphertext_buf.clear();
+                ciphertext_buf.extend_from_slice(text.as_bytes());
+
+                // Encrypt in place: `ciphertext_buf` holds the plaintext
+                // going in and the ciphertext + tag coming out, so this
+                // message costs no allocation beyond what the cipher
+                // itself needs internally.
+                if cipher_impl.encrypt_in_place(nonce, aad.as_bytes(), &mut ciphertext_buf).is_ok() {
+                  // Nonce lengths (12, 24) are multiples of 3, so base64
+                  // of the nonce alone never needs padding and
+                  // base64(nonce) + base64(ciphertext) equals
+                  // base64(nonce || ciphertext). That lets each half be
+                  // encoded straight into `base64_buf` without first
+                  // concatenating nonce and ciphertext into a combined
+                  // buffer.
+                  let encoded_len = (nonce_len / 3 * 4) + ciphertext_buf.len().div_ceil(3) * 4;
+                  base64_buf.clear();
+                  base64_buf.resize(encoded_len, 0);
+
+                  let written = BASE64.encode_slice(&*nonce, &mut base64_buf).ok().and_then(|n| {
+                    BASE64
+                      .encode_slice(&ciphertext_buf, &mut base64_buf[n..])
+                      .ok()
+                      .map(|c| n + c)
+                  });
+
+                  if let Some(written) = written {
+                    batch_buf.extend_from_slice(aad.as_bytes());
+                    batch_buf.push(b';');
+                    batch_buf.extend_from_slice(&base64_buf[..written]);
+                    batch_buf.push(b'\n');
+                    seq += 1;
+                  }
+                }
               }
+
+              LogMessage::Shutdown => shutdown = true,
             }
+          }
 
-            LogMessage::Shutdown => break,
+          let _ = file.write_all(&batch_buf);
+          let _ = file.flush();
+
+          if shutdown {
+            break 'outer;
           }
         }
       })?;
@@ -132,14 +538,17 @@ impl SecureLogger {
     // Initialize the global logger if not already initialized
     INIT.call_once(|| {
       log::set_logger(Box::leak(Box::new(logger.clone())))
-        .map(|()| log::set_max_level(LevelFilter::Trace))
+        .map(|()| log::set_max_level(STATIC_MAX_LEVEL))
         .expect("Failed to initialize logger");
     });
 
     Ok(logger)
   }
 
-  /// Decrypt a log file and write the contents to a new file
+  /// Decrypt a log file and return its contents as a single string
+  ///
+  /// This collects [`Self::decrypt_stream`] into memory, so it reads the
+  /// whole file up front. Prefer `decrypt_stream` for large log files.
   ///
   /// # Arguments
   /// * `key` - The same key used for encryption
@@ -150,63 +559,194 @@ impl SecureLogger {
   ///
   /// # Example
   /// ```no_run
+  /// use secrecy::Secret;
   /// use secure_log::SecureLogger;
   ///
   /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
-  /// let contents = SecureLogger::decrypt("my-secret-key", "application.log")?;
+  /// let contents = SecureLogger::decrypt(Secret::new("my-secret-key".to_string()), "application.log")?;
   /// std::fs::write("decrypted.log", contents)?;
   /// # Ok(())
   /// # }
   /// ```
   pub fn decrypt(
-    key: String,
+    key: Secret<String>,
     input_path: impl AsRef<Path>
   ) -> Result<String, Error> {
-    // Derive 32-byte key bytes
-    let key_bytes = Self::derive_key(&key);
+    let mut decrypted_contents = String::new();
 
-    // Initialize cipher with the key
-    let cipher = Aes256Gcm::new_from_slice(&key_bytes)
-      .map_err(|_| Error::InvalidKey)?;
+    for line in Self::decrypt_stream(key, input_path)? {
+      decrypted_contents.push_str(&line?);
+      decrypted_contents.push('\n');
+    }
+
+    Ok(decrypted_contents)
+  }
 
+  /// Decrypt a log file lazily, yielding one decrypted line at a time
+  ///
+  /// Unlike [`Self::decrypt`], this never holds more than a single entry
+  /// in memory, so it can process multi-gigabyte log files in constant
+  /// memory and is suitable for `grep`-style streaming pipelines.
+  ///
+  /// # Arguments
+  /// * `key` - The same key used for encryption
+  /// * `input_path` - Path to the encrypted log file
+  ///
+  /// # Example
+  /// ```no_run
+  /// use secrecy::Secret;
+  /// use secure_log::SecureLogger;
+  ///
+  /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+  /// for line in SecureLogger::decrypt_stream(Secret::new("my-secret-key".to_string()), "application.log")? {
+  ///   println!("{}", line?);
+  /// }
+  /// # Ok(())
+  /// # }
+  /// ```
+  pub fn decrypt_stream(
+    key: Secret<String>,
+    input_path: impl AsRef<Path>
+  ) -> Result<DecryptStream, Error> {
     // Open input and output files
     let input_file = File::open(input_path)?;
     let reader = BufReader::new(input_file);
-    let mut decrypted_contents = String::new();
+    let mut lines = reader.lines();
+
+    // The first line is either a plaintext KDF header (current format) or
+    // the first encrypted entry (legacy files predating the header and
+    // the sequence/level AAD framing that came with it)
+    let mut pending = None;
+    let mut cipher = Cipher::Aes256Gcm;
+    let mut has_header = false;
+    let key_bytes = match lines.next() {
+      Some(first_line) => {
+        let first_line = first_line?;
+
+        match KdfHeader::from_line(&first_line)? {
+          Some(header) => {
+            cipher = header.cipher;
+            has_header = true;
+            Self::derive_key_argon2(key.expose_secret(), &header)?
+          }
+          None => {
+            pending = Some(first_line);
+            DerivedKey(Self::derive_key(key.expose_secret()))
+          }
+        }
+      }
+      None => DerivedKey(Self::derive_key(key.expose_secret())),
+    };
 
-    // Process each line
-    for line in reader.lines() {
-      let line = line?;
+    // Initialize cipher with the key, then immediately drop the derived
+    // key bytes now that the cipher holds its own copy
+    let cipher_impl = CipherImpl::new(cipher, &key_bytes)?;
+    drop(key_bytes);
+
+    Ok(DecryptStream {
+      lines,
+      cipher_impl,
+      nonce_len: cipher.nonce_len(),
+      has_header,
+      expected_seq: 0,
+      pending,
+    })
+  }
 
-      // Decode the base64 line
-      let encrypted_data = BASE64.decode(line).map_err(|_| Error::InvalidData)?;
+  /// Derives a 32-byte encryption key using SHA-256
+  ///
+  /// Kept only so that files written before this crate salted its keys
+  /// can still be decrypted; new files always go through
+  /// [`Self::derive_key_argon2`].
+  fn derive_key(key: &str) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update(key.as_bytes());
+    hasher.finalize().to_vec()
+  }
 
-      // First 12 bytes are the nonce, rest is the encrypted message
-      if encrypted_data.len() < 12 {
+  /// Derives a 32-byte encryption key from `key` using Argon2id, salted
+  /// and parameterized per `header`
+  fn derive_key_argon2(key: &str, header: &KdfHeader) -> Result<DerivedKey, Error> {
+    let params = argon2::Params::new(header.m_cost, header.t_cost, header.p_cost, Some(32))
+      .map_err(|_| Error::InvalidKey)?;
+    let argon2 = Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params);
+
+    let mut key_bytes = vec![0u8; 32];
+    argon2
+      .hash_password_into(key.as_bytes(), &header.salt, &mut key_bytes)
+      .map_err(|_| Error::InvalidKey)?;
+
+    Ok(DerivedKey(key_bytes))
+  }
+}
+
+/// Iterator over the decrypted lines of a log file, produced by
+/// [`SecureLogger::decrypt_stream`]. Holds only the current line's data in
+/// memory at a time rather than accumulating the whole file.
+pub struct DecryptStream {
+  lines: Lines<BufReader<File>>,
+  cipher_impl: CipherImpl,
+  nonce_len: usize,
+  has_header: bool,
+  expected_seq: u64,
+  pending: Option<String>,
+}
+
+impl DecryptStream {
+  /// Decode and decrypt a single raw line from the file
+  fn decode_line(&mut self, line: &str) -> Result<String, Error> {
+    // Lines written with the header carry a "seq;level;" AAD prefix ahead
+    // of the base64 payload; legacy lines are the payload alone
+    let (aad, payload) = if self.has_header {
+      let mut fields = line.splitn(3, ';');
+      let seq_field = fields.next().ok_or(Error::InvalidData)?;
+      let level_field = fields.next().ok_or(Error::InvalidData)?;
+      let payload = fields.next().ok_or(Error::InvalidData)?;
+
+      let seq: u64 = seq_field.parse().map_err(|_| Error::InvalidData)?;
+      if seq != self.expected_seq {
         return Err(Error::InvalidData);
       }
+      self.expected_seq += 1;
 
-      let (nonce_bytes, encrypted_message) = encrypted_data.split_at(12);
-      let nonce = Nonce::from_slice(nonce_bytes);
+      (format!("{};{}", seq_field, level_field), payload)
+    } else {
+      (String::new(), line)
+    };
 
-      // Decrypt the message
-      let decrypted = cipher
-        .decrypt(nonce, encrypted_message)
-        .map_err(|_| Error::DecryptionFailed)?;
+    // Decode the base64 payload
+    let encrypted_data = BASE64.decode(payload).map_err(|_| Error::InvalidData)?;
 
-      // Convert to string and append
-      decrypted_contents.push_str(&String::from_utf8_lossy(&decrypted));
-      decrypted_contents.push('\n');
+    // First `nonce_len` bytes are the nonce, rest is the encrypted message
+    if encrypted_data.len() < self.nonce_len {
+      return Err(Error::InvalidData);
     }
 
-    Ok(decrypted_contents)
+    let (nonce_bytes, encrypted_message) = encrypted_data.split_at(self.nonce_len);
+
+    // Decrypt the message, verifying the seq/level AAD in the process
+    let decrypted = self
+      .cipher_impl
+      .decrypt(nonce_bytes, encrypted_message, aad.as_bytes())
+      .map_err(|_| Error::DecryptionFailed)?;
+
+    Ok(String::from_utf8_lossy(&decrypted).into_owned())
   }
+}
 
-  /// Derives a 32-byte encryption key using SHA-256
-  fn derive_key(key: &str) -> Vec<u8> {
-    let mut hasher = Sha256::new();
-    hasher.update(key.as_bytes());
-    hasher.finalize().to_vec()
+impl Iterator for DecryptStream {
+  type Item = Result<String, Error>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let line = match self.pending.take() {
+      Some(line) => line,
+      None => match self.lines.next()? {
+        Ok(line) => line,
+        Err(err) => return Some(Err(err.into())),
+      },
+    };
+
+    Some(self.decode_line(&line))
   }
 }
 
@@ -222,7 +762,7 @@ impl Clone for SecureLogger {
 
 impl log::Log for SecureLogger {
   fn enabled(&self, metadata: &Metadata) -> bool {
-    metadata.level() <= Level::Trace
+    metadata.level() <= STATIC_MAX_LEVEL
   }
 
   fn log(&self, record: &Record) {
@@ -236,7 +776,10 @@ impl log::Log for SecureLogger {
       );
 
       // Send to worker thread via channel
-      let _ = self.sender.send(LogMessage::Entry(log_entry));
+      let _ = self.sender.send(LogMessage::Entry {
+        level: record.level(),
+        text: log_entry,
+      });
     }
   }
 
@@ -251,3 +794,170 @@ impl Drop for SecureLogger {
     let _ = self.sender.send(LogMessage::Shutdown);
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use log::Log;
+  use std::thread;
+  use std::time::Duration;
+
+  /// The worker thread processes entries asynchronously and `Drop` doesn't
+  /// join it, so tests give it a moment to write before reading the file
+  /// back, the same way `examples/simple_logging.rs` does.
+  const WORKER_SETTLE: Duration = Duration::from_millis(200);
+
+  fn temp_log_path(name: &str) -> PathBuf {
+    let mut path = std::env::temp_dir();
+    path.push(format!("secure_log_test_{}_{}.log", std::process::id(), name));
+    let _ = std::fs::remove_file(&path);
+    path
+  }
+
+  fn log_record(logger: &SecureLogger, level: Level, message: &str) {
+    logger.log(&Record::builder().level(level).args(format_args!("{}", message)).build());
+  }
+
+  fn round_trip_for(cipher: Cipher, name: &str) {
+    let path = temp_log_path(name);
+    let logger =
+      SecureLogger::encrypt(Secret::new("test-passphrase".into()), &path, cipher).unwrap();
+
+    log_record(&logger, Level::Info, "hello from the round trip test");
+    thread::sleep(WORKER_SETTLE);
+
+    let decrypted = SecureLogger::decrypt(Secret::new("test-passphrase".into()), &path).unwrap();
+    assert!(decrypted.contains("hello from the round trip test"));
+
+    let _ = std::fs::remove_file(&path);
+  }
+
+  #[test]
+  fn round_trip_aes256gcm() {
+    round_trip_for(Cipher::Aes256Gcm, "round_trip_aes");
+  }
+
+  #[test]
+  fn round_trip_xchacha20poly1305() {
+    round_trip_for(Cipher::XChaCha20Poly1305, "round_trip_xchacha");
+  }
+
+  #[test]
+  fn decrypt_stream_matches_decrypt() {
+    let path = temp_log_path("stream_parity");
+    let logger =
+      SecureLogger::encrypt(Secret::new("test-passphrase".into()), &path, Cipher::Aes256Gcm)
+        .unwrap();
+
+    for message in ["first line", "second line", "third line"] {
+      log_record(&logger, Level::Warn, message);
+    }
+    thread::sleep(WORKER_SETTLE);
+
+    let collected = SecureLogger::decrypt(Secret::new("test-passphrase".into()), &path).unwrap();
+    let collected_lines: Vec<&str> = collected.lines().collect();
+
+    let streamed: Vec<String> =
+      SecureLogger::decrypt_stream(Secret::new("test-passphrase".into()), &path)
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    assert_eq!(streamed.len(), 3);
+    assert_eq!(
+      collected_lines,
+      streamed.iter().map(String::as_str).collect::<Vec<_>>()
+    );
+
+    let _ = std::fs::remove_file(&path);
+  }
+
+  #[test]
+  fn tampered_sequence_is_rejected() {
+    let path = temp_log_path("tamper");
+    let logger =
+      SecureLogger::encrypt(Secret::new("test-passphrase".into()), &path, Cipher::Aes256Gcm)
+        .unwrap();
+
+    log_record(&logger, Level::Error, "one");
+    log_record(&logger, Level::Error, "two");
+    thread::sleep(WORKER_SETTLE);
+
+    // Drop the seq=0 entry so the remaining line starts at seq=1, simulating
+    // a deleted/reordered line an attacker could produce by editing the file.
+    let contents = std::fs::read_to_string(&path).unwrap();
+    let mut lines: Vec<&str> = contents.lines().collect();
+    assert_eq!(lines.len(), 3, "expected a header line plus two entries");
+    lines.remove(1);
+    std::fs::write(&path, lines.join("\n") + "\n").unwrap();
+
+    let result = SecureLogger::decrypt(Secret::new("test-passphrase".into()), &path);
+    assert!(matches!(result, Err(Error::InvalidData)));
+
+    let _ = std::fs::remove_file(&path);
+  }
+
+  #[test]
+  fn legacy_file_without_header_decrypts() {
+    let path = temp_log_path("legacy");
+    let passphrase = "legacy-passphrase";
+
+    // Hand-build a pre-header file: unsalted SHA-256 key, no AAD, a single
+    // base64(nonce || ciphertext) line, exactly what this crate produced
+    // before the KDF header and AAD framing existed.
+    let key_bytes = SecureLogger::derive_key(passphrase);
+    let cipher_impl = CipherImpl::new(Cipher::Aes256Gcm, &key_bytes).unwrap();
+
+    let mut nonce = vec![0u8; Cipher::Aes256Gcm.nonce_len()];
+    OsRng.fill_bytes(&mut nonce);
+
+    let mut buffer = b"a legacy log line".to_vec();
+    cipher_impl.encrypt_in_place(&nonce, b"", &mut buffer).unwrap();
+
+    let mut combined = nonce;
+    combined.extend_from_slice(&buffer);
+    std::fs::write(&path, format!("{}\n", BASE64.encode(&combined))).unwrap();
+
+    let decrypted = SecureLogger::decrypt(Secret::new(passphrase.to_string()), &path).unwrap();
+    assert_eq!(decrypted.trim_end(), "a legacy log line");
+
+    let _ = std::fs::remove_file(&path);
+  }
+
+  #[test]
+  fn unrecognized_cipher_field_is_rejected() {
+    let path = temp_log_path("unknown_cipher");
+
+    // A header with the magic prefix present but a `cipher=` value that
+    // names no known algorithm must be rejected outright rather than
+    // silently falling back to AES-256-GCM.
+    std::fs::write(
+      &path,
+      "secure_log/v1;kdf=argon2id;cipher=made-up-cipher;m=19456;t=2;p=1;salt=AAAAAAAAAAAAAAAAAAAAAA==\n",
+    )
+    .unwrap();
+
+    let result = SecureLogger::decrypt(Secret::new("whatever".to_string()), &path);
+    assert!(matches!(result, Err(Error::InvalidData)));
+
+    let _ = std::fs::remove_file(&path);
+  }
+
+  #[test]
+  fn oversized_argon2_params_are_rejected() {
+    let path = temp_log_path("oversized_argon2_params");
+
+    // A header claiming an absurd `m=` must be rejected before it ever
+    // reaches `Argon2::hash_password_into`, which has no ceiling of its own.
+    std::fs::write(
+      &path,
+      "secure_log/v1;kdf=argon2id;cipher=aes256gcm;m=4000000000;t=1;p=1;salt=AAAAAAAAAAAAAAAAAAAAAA==\n",
+    )
+    .unwrap();
+
+    let result = SecureLogger::decrypt(Secret::new("whatever".to_string()), &path);
+    assert!(matches!(result, Err(Error::InvalidData)));
+
+    let _ = std::fs::remove_file(&path);
+  }
+}